@@ -1,6 +1,10 @@
 mod pmd;
+mod stats;
 
-use crate::pmd::{adjust_device_timestamp, PmdUsb, SensorValues};
+use crate::pmd::{
+    ConfigOverrides, PmdError, PmdUsb, SensorValues, PMD_ADC_CH_NUM, PMD_SENSOR_CH_NUM,
+};
+use crate::stats::Stats;
 use clap::{Arg, Command};
 use csv::Writer;
 use std::fs::File;
@@ -17,6 +21,10 @@ struct Config {
     timeout: Duration,
 }
 
+const SENSOR_NAMES: [&str; PMD_SENSOR_CH_NUM] = [
+    "PCIE1_V", "PCIE1_I", "PCIE2_V", "PCIE2_I", "EPS1_V", "EPS1_I", "EPS2_V", "EPS2_I",
+];
+
 fn main() {
     env_logger::init();
 
@@ -64,11 +72,68 @@ fn main() {
                 .help("Output file to write to (leave empty to write to STDOUT)")
                 .num_args(0..=1), // At most one argument
         )
+        .arg(
+            Arg::new("read-config")
+                .long("read-config")
+                .help("Read and print the current device configuration, then exit")
+                .action(clap::ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new("adc-offset")
+                .long("adc-offset")
+                .value_name("OFFSETS")
+                .help("Comma-separated ADC offset calibration, one value per channel (8 total)")
+                .value_delimiter(','),
+        )
+        .arg(
+            Arg::new("adc-gain-offset")
+                .long("adc-gain-offset")
+                .value_name("OFFSETS")
+                .help("Comma-separated ADC gain offset calibration, one value per channel")
+                .value_delimiter(','),
+        )
+        .arg(
+            Arg::new("averaging")
+                .long("averaging")
+                .value_name("SAMPLES")
+                .help("Set the device-side averaging window"),
+        )
+        .arg(
+            Arg::new("oled-disable")
+                .long("oled-disable")
+                .value_name("0|1")
+                .help("Disable the onboard OLED display"),
+        )
+        .arg(
+            Arg::new("oled-rotation")
+                .long("oled-rotation")
+                .value_name("ROTATION")
+                .help("Set the onboard OLED display rotation"),
+        )
+        .arg(
+            Arg::new("timeout-count")
+                .long("timeout-count")
+                .value_name("COUNT")
+                .help("Set the device-side timeout count"),
+        )
+        .arg(
+            Arg::new("timeout-action")
+                .long("timeout-action")
+                .value_name("ACTION")
+                .help("Set the device-side timeout action"),
+        )
+        .arg(
+            Arg::new("stats")
+                .long("stats")
+                .help("Print per-channel min/max/mean/EMA and integrated energy on exit")
+                .action(clap::ArgAction::SetTrue),
+        )
         .get_matches();
 
     /* Dispatch command line options */
     let port_name = args.get_one::<String>("port").unwrap();
     let output = args.get_one::<String>("output").cloned();
+    let collect_stats = args.get_flag("stats");
 
     let config = Config {
         speed_level: args
@@ -103,21 +168,58 @@ fn main() {
     let mut pmd_usb = PmdUsb::new(port_name);
 
     /* Set up device */
-    pmd_usb.init();
+    pmd_usb.init().expect("Failed to initialize device");
+
+    /* Dump the current device configuration and exit, if requested */
+    if args.get_flag("read-config") {
+        let config = pmd_usb.read_config().expect("Failed to read device config");
+        println!("{:#?}", config);
+        return;
+    }
+
+    /* Apply any configuration overrides and exit */
+    let config_overrides = ConfigOverrides {
+        adc_offset: parse_i8_array(&args, "adc-offset"),
+        adc_gain_offset: parse_i8_array(&args, "adc-gain-offset"),
+        averaging: parse_arg::<u8>(&args, "averaging"),
+        oled_disable: parse_arg::<u8>(&args, "oled-disable"),
+        oled_rotation: parse_arg::<u8>(&args, "oled-rotation"),
+        timeout_count: parse_arg::<u16>(&args, "timeout-count"),
+        timeout_action: parse_arg::<u8>(&args, "timeout-action"),
+    };
+    if config_overrides.adc_offset.is_some()
+        || config_overrides.adc_gain_offset.is_some()
+        || config_overrides.averaging.is_some()
+        || config_overrides.oled_disable.is_some()
+        || config_overrides.oled_rotation.is_some()
+        || config_overrides.timeout_count.is_some()
+        || config_overrides.timeout_action.is_some()
+    {
+        pmd_usb
+            .write_config(&config_overrides)
+            .expect("Failed to write device config");
+        return;
+    }
 
     /* Prepare main loop depending on speed level */
     match config.speed_level {
         /* At this speed level, we simply print once and exit */
         0 => {
-            let sensors = pmd_usb.read_sensors();
+            let sensors = pmd_usb.read_sensors().expect("Failed to read sensors");
             println!("{:?}", sensors.sensor);
             return;
         }
         /* Prepare for continuous TX */
-        2 => pmd_usb.enable_cont_tx(),
+        2 => pmd_usb
+            .enable_cont_tx()
+            .expect("Failed to enable cont TX"),
         3 => {
-            pmd_usb.bump_baud_rate();
-            pmd_usb.enable_cont_tx();
+            pmd_usb
+                .bump_baud_rate()
+                .expect("Failed to bump baud rate");
+            pmd_usb
+                .enable_cont_tx()
+                .expect("Failed to enable cont TX");
         }
         /* Speed level out of range */
         _ if config.speed_level > 3 => {
@@ -147,9 +249,7 @@ fn main() {
     let (tx, rx) = channel::<(u128, SensorValues)>();
 
     /* Create a new thread for writing the output file */
-    let writer_handle = thread::spawn(move || {
-        log_to_csv(output, rx, running_w);
-    });
+    let writer_handle = thread::spawn(move || log_to_csv(output, rx, running_w, collect_stats));
 
     /* Switch polling method based on speed level */
     let read_pmd = match config.speed_level {
@@ -170,10 +270,32 @@ fn main() {
     /* Start the main loop */
     while running.load(Ordering::SeqCst) {
         /* Read sensor values depending on the current polling method */
-        (timestamp, sensor_values) = read_pmd(&mut pmd_usb, &config);
-
-        /* Send current sensor values to the writer */
-        tx.send((timestamp, sensor_values)).unwrap();
+        match read_pmd(&mut pmd_usb, &config) {
+            Ok(reading) => {
+                (timestamp, sensor_values) = reading;
+                /* Send current sensor values to the writer */
+                tx.send((timestamp, sensor_values)).unwrap();
+            }
+            /* A transient USB hiccup shouldn't kill a long capture: try to
+             * reopen the port and resume in the same mode instead of aborting */
+            Err(e) => {
+                log::warn!("Lost connection to device ({}), reconnecting...", e);
+                match pmd_usb.reconnect() {
+                    Ok(_) => {
+                        if config.speed_level >= 2 {
+                            if let Err(e) = pmd_usb.enable_cont_tx() {
+                                log::error!("Failed to re-enable cont TX after reconnect: {}", e);
+                                running.store(false, Ordering::SeqCst);
+                            }
+                        }
+                    }
+                    Err(e) => {
+                        log::error!("Failed to reconnect: {}", e);
+                        running.store(false, Ordering::SeqCst);
+                    }
+                }
+            }
+        }
     }
 
     /* Join the timeout thread, if possible */
@@ -182,22 +304,33 @@ fn main() {
     }
 
     /* Join the CSV writer */
-    writer_handle.join().expect("Failed to join writer thread");
+    let stats = writer_handle.join().expect("Failed to join writer thread");
+
+    /* Print the accumulated statistics, if requested */
+    if let Some(stats) = stats {
+        stats.print_summary();
+    }
 
     /* Reset the device */
     match config.speed_level {
-        2 => pmd_usb.disable_cont_tx(),
+        2 => pmd_usb
+            .disable_cont_tx()
+            .expect("Failed to disable cont TX"),
         3 => {
-            pmd_usb.disable_cont_tx();
-            pmd_usb.restore_baud_rate();
+            pmd_usb
+                .disable_cont_tx()
+                .expect("Failed to disable cont TX");
+            pmd_usb
+                .restore_baud_rate()
+                .expect("Failed to restore baud rate");
         }
         _ => {}
     }
 }
 
-fn read_pmd_slow(pmd_usb: &mut PmdUsb, config: &Config) -> (u128, SensorValues) {
+fn read_pmd_slow(pmd_usb: &mut PmdUsb, config: &Config) -> Result<(u128, SensorValues), PmdError> {
     let start = std::time::Instant::now();
-    let _sensor_values = pmd_usb.read_sensor_values();
+    let _sensor_values = pmd_usb.read_sensor_values()?;
     let elapsed = start.elapsed();
     println!("{}", elapsed.as_micros());
     let timestamp = get_host_timestamp();
@@ -207,22 +340,23 @@ fn read_pmd_slow(pmd_usb: &mut PmdUsb, config: &Config) -> (u128, SensorValues)
     } else {
         Duration::new(0, 0)
     });
-    (timestamp, sensor_values)
+    Ok((timestamp, sensor_values))
 }
 
-fn read_pmd_fast(pmd_usb: &mut PmdUsb, config: &Config) -> (u128, SensorValues) {
-    let timed_adc_buffer = pmd_usb.read_cont_tx();
+fn read_pmd_fast(pmd_usb: &mut PmdUsb, config: &Config) -> Result<(u128, SensorValues), PmdError> {
+    let timed_adc_buffer = pmd_usb.read_cont_tx()?;
     let adc_buffer = timed_adc_buffer.buffer;
-    let timestamp = adjust_device_timestamp(timed_adc_buffer.timestamp);
+    let timestamp = pmd_usb.adjust_device_timestamp(timed_adc_buffer.timestamp);
     let sensor_values = pmd_usb.convert_adc_values(&adc_buffer);
-    (timestamp, sensor_values)
+    Ok((timestamp, sensor_values))
 }
 
 fn log_to_csv(
     output: Option<String>,
     rx: Receiver<(u128, SensorValues)>,
     running: Arc<AtomicBool>,
-) {
+    collect_stats: bool,
+) -> Option<Stats> {
     /* Choose either an output file or STDOUT */
     let sink: Box<dyn Write> = match output {
         Some(path) => Box::new(File::create(path).expect("Failed to create output file")),
@@ -234,24 +368,22 @@ fn log_to_csv(
 
     /* Print the CSV header */
     csv_writer
-        .write_record([
-            "timestamp",
-            "PCIE1_V",
-            "PCIE1_I",
-            "PCIE2_V",
-            "PCIE2_I",
-            "EPS1_V",
-            "EPS1_I",
-            "EPS2_V",
-            "EPS2_I",
-        ])
+        .write_record(std::iter::once("timestamp").chain(SENSOR_NAMES))
         .expect("Failed to write CSV header");
 
+    /* Only accumulated when the --stats flag is set */
+    let mut stats = collect_stats.then(|| Stats::new(SENSOR_NAMES));
+
     let mut timestamp: u128;
     let mut sensor_values: SensorValues;
 
     while running.load(Ordering::SeqCst) {
         (timestamp, sensor_values) = rx.recv().unwrap();
+
+        if let Some(stats) = stats.as_mut() {
+            stats.update(timestamp, &sensor_values);
+        }
+
         let sensor_values_string: Vec<String> =
             sensor_values.iter().map(|v| v.to_string()).collect();
         csv_writer
@@ -262,6 +394,29 @@ fn log_to_csv(
             .expect("Failed to write CSV record");
         csv_writer.flush().expect("Failed to flush CSV writer");
     }
+
+    stats
+}
+
+fn parse_arg<T: std::str::FromStr>(args: &clap::ArgMatches, id: &str) -> Option<T>
+where
+    T::Err: std::fmt::Debug,
+{
+    args.get_one::<String>(id)
+        .map(|value| value.parse::<T>().expect("Invalid value for option"))
+}
+
+fn parse_i8_array(args: &clap::ArgMatches, id: &str) -> Option<[i8; PMD_ADC_CH_NUM]> {
+    let values: Vec<&String> = args.get_many::<String>(id)?.collect();
+    let values: Vec<i8> = values
+        .iter()
+        .map(|value| value.parse::<i8>().expect("Invalid value for option"))
+        .collect();
+    Some(
+        values
+            .try_into()
+            .expect("Expected exactly 8 comma-separated values"),
+    )
 }
 
 fn check_port_validity(port_name: &str) -> bool {
@@ -1,6 +1,8 @@
 use bincode::{deserialize, serialize};
 use serde::{Deserialize, Serialize};
 use serialport::SerialPort;
+use std::collections::VecDeque;
+use std::fmt;
 use std::fmt::Debug;
 use std::io::Write;
 use std::thread;
@@ -9,6 +11,10 @@ use std::time::Duration;
 const BAUDRATE_DEFAULT: u32 = 115_200;
 const BAUDRATE_FASTEST: u32 = 460_800; //345_600;//230_400;
 
+/// Total number of attempts a `transact` request/response exchange makes
+/// before giving up and returning the last error to the caller
+const TRANSACT_RETRIES: u32 = 3;
+
 pub const PMD_WELCOME_RESPONSE: &[u8; 17] = b"ElmorLabs PMD-USB";
 pub const PMD_ADC_CH_NUM: usize = 8;
 pub const PMD_ADC_BYTE_NUM: usize = size_of::<u16>() * PMD_ADC_CH_NUM;
@@ -26,6 +32,20 @@ const PMD_SENSOR_CURRENT_SCALE: f64 = 1.0 / 10.0;
 const PMD_CLOCK_MULTIPLIER: f64 = 1.0 / 3.0;
 const PMD_TIMEOUT_SECS: u64 = 1;
 
+/// Tolerance (in raw device timer counts) for reordered reads before a
+/// drop in the raw timestamp is treated as a counter wraparound
+const TIMESTAMP_WRAP_TOLERANCE: u32 = 1_000;
+
+/// Largest plausible gap (in raw device timer counts) between two
+/// consecutive cont-TX records, used to detect a desynced byte stream.
+/// At the ~3 MHz device clock this is a generous 1 second.
+const CONT_TX_MAX_TIMESTAMP_STEP: u32 = 3_000_000;
+
+/// Number of consecutive byte-read timeouts tolerated in `read_cont_tx`
+/// before the stream is considered stalled rather than merely paused.
+/// At the 100 ms port timeout this is a generous 5 seconds.
+const CONT_TX_MAX_CONSECUTIVE_TIMEOUTS: u32 = 50;
+
 pub const CONFIG_NO: u8 = 0x00;
 pub const CONFIG_YES: u8 = 0x01;
 pub const CONFIG_MASK_NONE: u8 = 0x00;
@@ -42,7 +62,41 @@ pub type SensorBuffer = [u16; PMD_SENSOR_CH_NUM];
 pub type AdcBuffer = [u16; PMD_ADC_CH_NUM];
 pub type SensorValues = [f64; PMD_SENSOR_CH_NUM];
 
+/// Errors that can occur while talking to the PMD over the serial link.
+///
+/// All of these are considered recoverable: a caller may retry the
+/// operation or reopen the port rather than aborting the whole session.
+#[derive(Debug)]
+pub enum PmdError {
+    Io(std::io::Error),
+    Protocol(String),
+}
+
+impl fmt::Display for PmdError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PmdError::Io(e) => write!(f, "serial I/O error: {}", e),
+            PmdError::Protocol(msg) => write!(f, "protocol error: {}", msg),
+        }
+    }
+}
+
+impl std::error::Error for PmdError {}
+
+impl From<std::io::Error> for PmdError {
+    fn from(e: std::io::Error) -> Self {
+        PmdError::Io(e)
+    }
+}
+
+impl From<serialport::Error> for PmdError {
+    fn from(e: serialport::Error) -> Self {
+        PmdError::Protocol(e.to_string())
+    }
+}
+
 #[repr(u8)]
+#[derive(Clone, Copy)]
 pub enum UartCommand {
     Welcome,
     ReadId,
@@ -139,57 +193,147 @@ pub struct UartConfigStruct {
     pub stop_bits: u32,
 }
 
+/// User-supplied overrides for a subset of `ConfigStruct` fields, applied
+/// on top of the device's current configuration by `PmdUsb::write_config`
+#[derive(Default)]
+pub struct ConfigOverrides {
+    pub adc_offset: Option<[i8; PMD_ADC_CH_NUM]>,
+    pub adc_gain_offset: Option<[i8; PMD_ADC_CH_NUM]>,
+    pub averaging: Option<u8>,
+    pub oled_disable: Option<u8>,
+    pub oled_rotation: Option<u8>,
+    pub timeout_count: Option<u16>,
+    pub timeout_action: Option<u8>,
+}
+
 pub struct PmdUsb {
     port: Box<dyn SerialPort>,
+    port_name: String,
+    baud_rate: u32,
     device_id: DeviceIdStruct,
     config: ConfigStruct,
     sensors: SensorStruct,
+    prev_device_timestamp: u32,
+    device_timestamp_high: u64,
+    last_adjusted_timestamp: u128,
+    cont_tx_synced: bool,
+    cont_tx_buffer: VecDeque<u8>,
+    cont_tx_dropped_bytes: u64,
 }
 
 impl PmdUsb {
     pub fn new(port_name: &str) -> Self {
-        let port = serialport::new(port_name, BAUDRATE_DEFAULT)
+        let port = Self::open(port_name, BAUDRATE_DEFAULT).expect("Unable to open serial port");
+
+        PmdUsb {
+            port,
+            port_name: port_name.to_string(),
+            baud_rate: BAUDRATE_DEFAULT,
+            device_id: DeviceIdStruct::default(),
+            config: ConfigStruct::default(),
+            sensors: SensorStruct::default(),
+            prev_device_timestamp: 0,
+            device_timestamp_high: 0,
+            last_adjusted_timestamp: 0,
+            cont_tx_synced: false,
+            cont_tx_buffer: VecDeque::new(),
+            cont_tx_dropped_bytes: 0,
+        }
+    }
+
+    fn open(port_name: &str, baud_rate: u32) -> Result<Box<dyn SerialPort>, PmdError> {
+        let port = serialport::new(port_name, baud_rate)
             .timeout(Duration::from_millis(100))
             .data_bits(serialport::DataBits::Eight)
             .stop_bits(serialport::StopBits::One)
             .parity(serialport::Parity::None)
             // .flow_control(serialport::FlowControl::Hardware)
             .open()
-            .expect("Unable to open serial port");
+            .map_err(|e| PmdError::Protocol(format!("unable to open serial port: {}", e)))?;
+        Ok(port)
+    }
 
-        PmdUsb {
-            port,
-            device_id: DeviceIdStruct::default(),
-            config: ConfigStruct::default(),
-            sensors: SensorStruct::default(),
+    /// Reopen the serial port and re-run the device init sequence, used to
+    /// recover from a transient USB hiccup without aborting a long-running
+    /// logging session.
+    ///
+    /// A hiccup severe enough to need this usually means the device itself
+    /// re-enumerated, so it comes back up at `BAUDRATE_DEFAULT` regardless
+    /// of what was negotiated before; reopen there and re-bump the baud
+    /// rate afterwards if it had been raised.
+    pub fn reconnect(&mut self) -> Result<(), PmdError> {
+        log::warn!("Reconnecting to {}", self.port_name);
+        let was_bumped = self.baud_rate != BAUDRATE_DEFAULT;
+
+        self.port = Self::open(&self.port_name, BAUDRATE_DEFAULT)?;
+        self.baud_rate = BAUDRATE_DEFAULT;
+        self.init()?;
+
+        if was_bumped {
+            self.bump_baud_rate()?;
         }
+
+        /* The device timer genuinely restarts from scratch after
+         * reconnecting, but the logged timestamp stream must not jump
+         * backwards: fold the last accumulated raw tick count into the
+         * high word so the next sample continues forward from where the
+         * previous session left off, instead of re-introducing the very
+         * backwards jump adjust_device_timestamp's monotonic clamp exists
+         * to prevent. */
+        self.device_timestamp_high += self.prev_device_timestamp as u64;
+        self.prev_device_timestamp = 0;
+        self.cont_tx_synced = false;
+        self.cont_tx_buffer.clear();
+
+        Ok(())
     }
 
-    fn send_command(&mut self, command: UartCommand) {
-        self.clear_buffers();
+    fn send_command(&mut self, command: UartCommand) -> Result<(), PmdError> {
+        self.clear_buffers()?;
         let tx_buffer = command as u8;
-        match self.port.write(&[tx_buffer]) {
-            Ok(_) => log::debug!("Sending command {:#04X} to device", tx_buffer),
-            Err(e) => panic!("Error while writing to device: {}", e),
-        }
-        self.port.flush().unwrap();
+        self.port.write(&[tx_buffer])?;
+        log::debug!("Sending command {:#04X} to device", tx_buffer);
+        self.port.flush()?;
+        Ok(())
     }
 
-    fn send_data(&mut self, data: &[u8]) {
-        self.clear_buffers();
-        match self.port.write_all(data) {
-            Ok(_) => log::debug!("Sending data to device: {:?}", data),
-            Err(e) => panic!("Error while writing to device: {}", e),
-        }
-        self.port.flush().unwrap();
+    fn send_data(&mut self, data: &[u8]) -> Result<(), PmdError> {
+        self.clear_buffers()?;
+        self.port.write_all(data)?;
+        log::debug!("Sending data to device: {:?}", data);
+        self.port.flush()?;
+        Ok(())
     }
 
-    fn read_data(&mut self, expect: usize) -> Vec<u8> {
+    fn read_data(&mut self, expect: usize) -> Result<Vec<u8>, PmdError> {
         let mut rx_buffer = vec![0u8; expect];
-        match self.port.read_exact(&mut rx_buffer) {
-            Ok(_) => rx_buffer,
-            Err(e) => panic!("Error while reading from device: {}", e),
+        self.port.read_exact(&mut rx_buffer)?;
+        Ok(rx_buffer)
+    }
+
+    /// Run a single command/response exchange, making up to
+    /// `TRANSACT_RETRIES` attempts in total (re-clearing buffers between
+    /// attempts) if the serial link hiccups along the way
+    fn transact(&mut self, command: UartCommand, expected_len: usize) -> Result<Vec<u8>, PmdError> {
+        let mut last_err = None;
+        for attempt in 1..=TRANSACT_RETRIES {
+            match self
+                .send_command(command)
+                .and_then(|_| self.read_data(expected_len))
+            {
+                Ok(response) => return Ok(response),
+                Err(e) => {
+                    log::warn!(
+                        "Transaction attempt {}/{} failed: {}",
+                        attempt,
+                        TRANSACT_RETRIES,
+                        e
+                    );
+                    last_err = Some(e);
+                }
+            }
         }
+        Err(last_err.unwrap())
     }
 
     fn convert_voltage_sensor_values(&self, value: u16) -> f64 {
@@ -232,106 +376,253 @@ impl PmdUsb {
         _adc_values
     }
 
-    pub fn welcome(&mut self) {
-        self.send_command(UartCommand::Welcome);
-        let response = self.read_data(PMD_WELCOME_RESPONSE.len());
-        assert_eq!(response, PMD_WELCOME_RESPONSE);
+    pub fn welcome(&mut self) -> Result<(), PmdError> {
+        let response = self.transact(UartCommand::Welcome, PMD_WELCOME_RESPONSE.len())?;
+        if response != PMD_WELCOME_RESPONSE {
+            return Err(PmdError::Protocol(format!(
+                "unexpected welcome response: {:?}",
+                response
+            )));
+        }
         log::debug!("> {}", std::str::from_utf8(&response).unwrap());
+        Ok(())
     }
 
-    pub fn read_device_id(&mut self) -> DeviceIdStruct {
-        self.clear_buffers();
-        self.send_command(UartCommand::ReadId);
-        let rx_buffer = self.read_data(size_of::<DeviceIdStruct>());
-        let device_id: DeviceIdStruct = deserialize(&rx_buffer).unwrap();
-        assert_eq!(device_id.product, PMD_USB_PRODUCT_ID, "Invalid product ID");
-        assert_eq!(device_id.vendor, PMD_USB_VENDOR_ID, "Invalid vendor ID");
+    pub fn read_device_id(&mut self) -> Result<DeviceIdStruct, PmdError> {
+        let rx_buffer = self.transact(UartCommand::ReadId, size_of::<DeviceIdStruct>())?;
+        let device_id: DeviceIdStruct = deserialize(&rx_buffer)
+            .map_err(|e| PmdError::Protocol(format!("malformed device ID: {}", e)))?;
+        if device_id.product != PMD_USB_PRODUCT_ID {
+            return Err(PmdError::Protocol("invalid product ID".to_string()));
+        }
+        if device_id.vendor != PMD_USB_VENDOR_ID {
+            return Err(PmdError::Protocol("invalid vendor ID".to_string()));
+        }
         log::debug!("> Running firmware version {}", device_id.firmware);
-        device_id
+        Ok(device_id)
     }
 
-    pub fn read_config(&mut self) -> ConfigStruct {
-        self.send_command(UartCommand::ReadConfig);
-        let rx_buffer = self.read_data(size_of::<ConfigStruct>());
-        let config: ConfigStruct = deserialize(&rx_buffer).unwrap();
-        config
+    pub fn read_config(&mut self) -> Result<ConfigStruct, PmdError> {
+        let rx_buffer = self.transact(UartCommand::ReadConfig, size_of::<ConfigStruct>())?;
+        deserialize(&rx_buffer).map_err(|e| PmdError::Protocol(format!("malformed config: {}", e)))
     }
 
-    pub fn write_config(&mut self) {
-        todo!();
+    pub fn write_config(&mut self, overrides: &ConfigOverrides) -> Result<(), PmdError> {
+        let mut config = self.read_config()?;
+
+        if let Some(adc_offset) = overrides.adc_offset {
+            config.adc_offset = adc_offset;
+        }
+        if let Some(adc_gain_offset) = overrides.adc_gain_offset {
+            config.adc_gain_offset = adc_gain_offset;
+        }
+        if let Some(averaging) = overrides.averaging {
+            config.averaging = averaging;
+        }
+        if let Some(oled_disable) = overrides.oled_disable {
+            config.oled_disable = oled_disable;
+        }
+        if let Some(oled_rotation) = overrides.oled_rotation {
+            config.oled_rotation = oled_rotation;
+        }
+        if let Some(timeout_count) = overrides.timeout_count {
+            config.timeout_count = timeout_count;
+        }
+        if let Some(timeout_action) = overrides.timeout_action {
+            config.timeout_action = timeout_action;
+        }
+
+        config.update_config_flag = CONFIG_YES;
+
+        /* The CRC is computed over the serialized struct with the CRC
+         * field itself zeroed out, so clear it before serializing */
+        config.crc = 0;
+        let mut tx_buffer = serialize(&config).unwrap();
+        let crc = crc16_ccitt(&tx_buffer);
+        tx_buffer[2..4].copy_from_slice(&crc.to_le_bytes());
+        config.crc = crc;
+
+        self.send_command(UartCommand::WriteConfig)?;
+        self.send_data(&tx_buffer)?;
+
+        self.config = config;
+        Ok(())
     }
 
-    pub fn read_sensors(&mut self) -> SensorStruct {
-        self.send_command(UartCommand::ReadSensors);
-        let rx_buffer = self.read_data(size_of::<SensorStruct>());
-        deserialize(&rx_buffer).unwrap()
+    pub fn read_sensors(&mut self) -> Result<SensorStruct, PmdError> {
+        let rx_buffer = self.transact(UartCommand::ReadSensors, size_of::<SensorStruct>())?;
+        deserialize(&rx_buffer)
+            .map_err(|e| PmdError::Protocol(format!("malformed sensor reading: {}", e)))
     }
 
-    pub fn read_sensor_values(&mut self) -> SensorBuffer {
-        self.send_command(UartCommand::ReadSensorValues);
-        let rx_buffer = self.read_data(PMD_SENSOR_BYTE_NUM);
-        deserialize(&rx_buffer).unwrap()
+    pub fn read_sensor_values(&mut self) -> Result<SensorBuffer, PmdError> {
+        let rx_buffer = self.transact(UartCommand::ReadSensorValues, PMD_SENSOR_BYTE_NUM)?;
+        deserialize(&rx_buffer)
+            .map_err(|e| PmdError::Protocol(format!("malformed sensor values: {}", e)))
     }
 
-    pub fn read_adc_buffer(&mut self) -> AdcBuffer {
-        self.send_command(UartCommand::ReadAdcBuffer);
-        let rx_buffer = self.read_data(PMD_ADC_BYTE_NUM);
-        deserialize(&rx_buffer).unwrap()
+    pub fn read_adc_buffer(&mut self) -> Result<AdcBuffer, PmdError> {
+        let rx_buffer = self.transact(UartCommand::ReadAdcBuffer, PMD_ADC_BYTE_NUM)?;
+        deserialize(&rx_buffer)
+            .map_err(|e| PmdError::Protocol(format!("malformed ADC buffer: {}", e)))
     }
 
-    pub fn read_cont_tx(&mut self) -> TimedAdcBuffer {
-        let n_bytes = size_of::<TimedAdcBuffer>();
-        let rx_buffer = self.read_data(n_bytes);
-        deserialize(&rx_buffer).unwrap()
+    /// Read the next cont-TX record, resynchronizing on the fly if the
+    /// byte stream has drifted out of alignment.
+    ///
+    /// There is no framing in the firmware's fixed-length records, so a
+    /// single dropped or extra byte on the UART would otherwise desync the
+    /// stream permanently. Instead, candidate records are read through a
+    /// rolling byte buffer and checked for plausibility (a monotonic-ish
+    /// device timestamp and correctly-padded ADC words); an implausible
+    /// candidate is discarded one byte at a time until alignment is
+    /// recovered.
+    ///
+    /// A single byte read timing out is expected during normal streaming
+    /// (the port timeout is much shorter than the polling interval) and is
+    /// simply retried, rather than treated as a lost connection - but
+    /// `CONT_TX_MAX_CONSECUTIVE_TIMEOUTS` in a row means the device has
+    /// stalled, and is reported as an error so the caller can reconnect.
+    pub fn read_cont_tx(&mut self) -> Result<TimedAdcBuffer, PmdError> {
+        let record_len = size_of::<TimedAdcBuffer>();
+        let mut consecutive_timeouts = 0u32;
+
+        loop {
+            while self.cont_tx_buffer.len() < record_len {
+                let mut byte = [0u8; 1];
+                match self.port.read_exact(&mut byte) {
+                    Ok(()) => {
+                        self.cont_tx_buffer.push_back(byte[0]);
+                        consecutive_timeouts = 0;
+                    }
+                    Err(e) if e.kind() == std::io::ErrorKind::TimedOut => {
+                        consecutive_timeouts += 1;
+                        if consecutive_timeouts > CONT_TX_MAX_CONSECUTIVE_TIMEOUTS {
+                            return Err(PmdError::Protocol(
+                                "cont-TX stream stalled: no data received".to_string(),
+                            ));
+                        }
+                        continue;
+                    }
+                    Err(e) => return Err(e.into()),
+                }
+            }
+
+            let candidate: Vec<u8> = self.cont_tx_buffer.iter().take(record_len).copied().collect();
+
+            if let Some(record) = self.parse_cont_tx_candidate(&candidate) {
+                self.cont_tx_buffer.drain(..record_len);
+                self.cont_tx_synced = true;
+                return Ok(record);
+            }
+
+            self.cont_tx_buffer.pop_front();
+            self.cont_tx_dropped_bytes += 1;
+            log::warn!(
+                "Dropped/realigned byte in cont-TX stream (total: {})",
+                self.cont_tx_dropped_bytes
+            );
+        }
     }
 
-    fn clear_buffers(&mut self) {
-        match self.port.clear(serialport::ClearBuffer::All) {
-            Ok(_) => (),
-            Err(e) => panic!("Error while clearing serial port: {}", e),
-        };
+    /// Decode and plausibility-check a candidate record from the cont-TX
+    /// stream, returning `None` if it looks like a desynced read
+    fn parse_cont_tx_candidate(&self, candidate: &[u8]) -> Option<TimedAdcBuffer> {
+        let record: TimedAdcBuffer = deserialize(candidate).ok()?;
+
+        if self.cont_tx_synced {
+            let delta = record.timestamp.wrapping_sub(self.prev_device_timestamp);
+            if delta == 0 || delta > CONT_TX_MAX_TIMESTAMP_STEP {
+                return None;
+            }
+        }
+
+        /* `i16_from_adc` shifts out the low 4 bits of each raw word, so a
+         * check on its *output* can never fail: every 12-bit result is
+         * trivially in range by construction. The low 4 bits it discards
+         * are unused padding that the firmware always emits as zero, so
+         * check those instead - a desynced read is likely to land on a
+         * byte pair whose padding bits aren't clear */
+        for &word in record.buffer.iter() {
+            if word & 0x000F != 0 {
+                return None;
+            }
+        }
+
+        Some(record)
     }
 
-    pub fn write_config_cont_tx(&mut self, config: &ContTxStruct) {
+    /// Scale the device-side timestamp (approx. 3 MHz) to monotonic microseconds.
+    ///
+    /// The raw counter is a `u32` that wraps roughly every 23 minutes, so a
+    /// raw value that drops well below the previous one (beyond a small
+    /// tolerance for out-of-order reads) is treated as a wraparound and
+    /// folded into an accumulated high word instead of being returned as-is.
+    /// A raw value that drops by less than that tolerance is exactly the
+    /// out-of-order case the tolerance exists for, so the result is also
+    /// clamped to never step backward from the last value returned.
+    pub fn adjust_device_timestamp(&mut self, raw_timestamp: u32) -> u128 {
+        if raw_timestamp.saturating_add(TIMESTAMP_WRAP_TOLERANCE) < self.prev_device_timestamp {
+            self.device_timestamp_high += 1u64 << 32;
+        }
+        self.prev_device_timestamp = raw_timestamp;
+
+        let accumulated = self.device_timestamp_high + raw_timestamp as u64;
+        let timestamp = (accumulated as f64 * PMD_CLOCK_MULTIPLIER).floor() as u128;
+
+        let timestamp = timestamp.max(self.last_adjusted_timestamp);
+        self.last_adjusted_timestamp = timestamp;
+        timestamp
+    }
+
+    fn clear_buffers(&mut self) -> Result<(), PmdError> {
+        self.port.clear(serialport::ClearBuffer::All)?;
+        Ok(())
+    }
+
+    pub fn write_config_cont_tx(&mut self, config: &ContTxStruct) -> Result<(), PmdError> {
         /* Tell the PMD to expect an incoming TX config */
-        self.send_command(UartCommand::WriteContTx);
+        self.send_command(UartCommand::WriteContTx)?;
 
         /* Serialize the configuration struct back into a byte vector */
         let tx_buffer = serialize(config).unwrap();
 
         /* Send configuration */
-        self.send_data(tx_buffer.as_slice());
+        self.send_data(tx_buffer.as_slice())?;
 
         /* Wait for the device to apply new config */
         log::debug!("Waiting for device to process configuration");
         thread::sleep(Duration::from_millis(100));
+        Ok(())
     }
 
-    pub fn enable_cont_tx(&mut self) {
-        self.clear_buffers();
+    pub fn enable_cont_tx(&mut self) -> Result<(), PmdError> {
+        self.clear_buffers()?;
+        self.cont_tx_buffer.clear();
+        self.cont_tx_synced = false;
         log::debug!("Starting cont TX");
         let config = ContTxStruct {
             enable: CONFIG_YES,
             timestamp_bytes: CONFIG_TIMESTAMP_FULL,
             adc_channels: CONFIG_MASK_ALL,
         };
-        self.write_config_cont_tx(&config);
+        self.write_config_cont_tx(&config)
     }
 
-    pub fn disable_cont_tx(&mut self) {
+    pub fn disable_cont_tx(&mut self) -> Result<(), PmdError> {
         log::debug!("Stopping cont TX");
         let config = ContTxStruct {
             enable: CONFIG_NO,
             timestamp_bytes: CONFIG_TIMESTAMP_FULL, //CONFIG_TIMESTAMP_NONE,
             adc_channels: CONFIG_MASK_ALL,          //CONFIG_MASK_NONE,
         };
-        self.write_config_cont_tx(&config);
-        self.clear_buffers();
+        self.write_config_cont_tx(&config)?;
+        self.clear_buffers()
     }
 
-    fn set_baud_rate(&mut self, baud_rate: u32) {
+    fn set_baud_rate(&mut self, baud_rate: u32) -> Result<(), PmdError> {
         log::debug!("Setting baud rate to {}", baud_rate);
-        self.send_command(UartCommand::WriteConfigUart);
+        self.send_command(UartCommand::WriteConfigUart)?;
         let config = UartConfigStruct {
             baud_rate,
             parity: CONFIG_UART_PARITY_NONE,
@@ -339,38 +630,48 @@ impl PmdUsb {
             stop_bits: CONFIG_UART_STOP_BITS_ONE,
         };
         let tx_buffer = serialize(&config).unwrap();
-        self.send_data(tx_buffer.as_slice());
+        self.send_data(tx_buffer.as_slice())?;
         thread::sleep(Duration::from_secs(PMD_TIMEOUT_SECS));
-        match self.port.set_baud_rate(baud_rate) {
-            Ok(_) => {}
-            Err(e) => panic!("Failed to set baud rate: {}", e),
-        }
+        self.port.set_baud_rate(baud_rate)?;
+        self.baud_rate = baud_rate;
         thread::sleep(Duration::from_millis(500));
-        self.clear_buffers();
+        self.clear_buffers()
     }
 
-    pub fn bump_baud_rate(&mut self) {
-        self.set_baud_rate(BAUDRATE_FASTEST);
+    pub fn bump_baud_rate(&mut self) -> Result<(), PmdError> {
+        self.set_baud_rate(BAUDRATE_FASTEST)
     }
 
-    pub fn restore_baud_rate(&mut self) {
-        self.set_baud_rate(BAUDRATE_DEFAULT);
+    pub fn restore_baud_rate(&mut self) -> Result<(), PmdError> {
+        self.set_baud_rate(BAUDRATE_DEFAULT)
     }
 
-    pub fn init(&mut self) {
-        self.disable_cont_tx();
-        self.clear_buffers();
-        self.device_id = self.read_device_id();
-        self.config = self.read_config();
-        self.sensors = self.read_sensors();
-        self.welcome();
+    pub fn init(&mut self) -> Result<(), PmdError> {
+        self.disable_cont_tx()?;
+        self.clear_buffers()?;
+        self.device_id = self.read_device_id()?;
+        self.config = self.read_config()?;
+        self.sensors = self.read_sensors()?;
+        self.welcome()
     }
 }
 
-/// Scale the device-side timestamp (approx. 3 MHz) to microseconds
-pub fn adjust_device_timestamp(timestamp: u32) -> u128 {
-    let _timestamp = timestamp as f64;
-    (_timestamp * PMD_CLOCK_MULTIPLIER).floor() as u128
+/// Compute a CRC16-CCITT checksum (init register 0xFFFF, polynomial 0x1021)
+/// over `data`, matching the algorithm expected by the PMD firmware's
+/// config validation
+fn crc16_ccitt(data: &[u8]) -> u16 {
+    let mut crc: u16 = 0xFFFF;
+    for &byte in data {
+        crc ^= (byte as u16) << 8;
+        for _ in 0..8 {
+            if (crc & 0x8000) != 0 {
+                crc = (crc << 1) ^ 0x1021;
+            } else {
+                crc <<= 1;
+            }
+        }
+    }
+    crc
 }
 
 /// Little helper to convert signed 12-bit integers from the ADC to i16
@@ -0,0 +1,110 @@
+use crate::pmd::{SensorValues, PMD_SENSOR_CH_NUM, PMD_SENSOR_NUM};
+
+/// Smoothing factor for the exponential moving average, 0 < alpha <= 1
+const EMA_ALPHA: f64 = 0.1;
+
+/// Microjoule-scale accumulator unit per Wh (3600 s/h * 1e6 us/s)
+const US_PER_WH: f64 = 3.6e9;
+
+/// Running min/max/mean/EMA for a single channel
+#[derive(Debug, Default, Clone, Copy)]
+pub struct ChannelStats {
+    pub min: f64,
+    pub max: f64,
+    pub mean: f64,
+    pub ema: f64,
+    count: u64,
+}
+
+impl ChannelStats {
+    fn update(&mut self, value: f64) {
+        if self.count == 0 {
+            self.min = value;
+            self.max = value;
+            self.mean = value;
+            self.ema = value;
+        } else {
+            self.min = self.min.min(value);
+            self.max = self.max.max(value);
+            self.mean += (value - self.mean) / (self.count + 1) as f64;
+            self.ema = EMA_ALPHA * value + (1.0 - EMA_ALPHA) * self.ema;
+        }
+        self.count += 1;
+    }
+}
+
+/// Trapezoidal energy integration for a single sensor pair (voltage * current)
+#[derive(Debug, Default, Clone, Copy)]
+struct EnergyAccumulator {
+    accumulated: f64,
+    prev_power: Option<f64>,
+    prev_timestamp: Option<u128>,
+}
+
+impl EnergyAccumulator {
+    fn update(&mut self, power: f64, timestamp: u128) {
+        if let (Some(prev_power), Some(prev_timestamp)) = (self.prev_power, self.prev_timestamp) {
+            /* A non-advancing or backwards timestamp can happen around a
+             * reconnect; skip the trapezoid rather than underflow the
+             * u128 subtraction */
+            if timestamp > prev_timestamp {
+                let dt = (timestamp - prev_timestamp) as f64;
+                self.accumulated += 0.5 * (prev_power + power) * dt;
+            }
+        }
+        self.prev_power = Some(power);
+        self.prev_timestamp = Some(timestamp);
+    }
+
+    fn watt_hours(&self) -> f64 {
+        self.accumulated / US_PER_WH
+    }
+}
+
+/// Per-channel running statistics plus per-sensor-pair integrated energy,
+/// accumulated over the lifetime of a logging session
+pub struct Stats {
+    names: [&'static str; PMD_SENSOR_CH_NUM],
+    channels: [ChannelStats; PMD_SENSOR_CH_NUM],
+    energy: [EnergyAccumulator; PMD_SENSOR_NUM],
+}
+
+impl Stats {
+    pub fn new(names: [&'static str; PMD_SENSOR_CH_NUM]) -> Self {
+        Stats {
+            names,
+            channels: Default::default(),
+            energy: Default::default(),
+        }
+    }
+
+    /// Fold a new sample into the running statistics and energy integrals
+    pub fn update(&mut self, timestamp: u128, sensor_values: &SensorValues) {
+        for (channel, &value) in self.channels.iter_mut().zip(sensor_values.iter()) {
+            channel.update(value);
+        }
+
+        for (i, accumulator) in self.energy.iter_mut().enumerate() {
+            let voltage = sensor_values[2 * i];
+            let current = sensor_values[2 * i + 1];
+            accumulator.update(voltage * current, timestamp);
+        }
+    }
+
+    /// Print a summary table of the accumulated statistics to STDOUT
+    pub fn print_summary(&self) {
+        println!("\n--- Summary ---");
+        for (name, channel) in self.names.iter().zip(self.channels.iter()) {
+            println!(
+                "{name}: min={:.03} max={:.03} mean={:.03} ema={:.03}",
+                channel.min, channel.max, channel.mean, channel.ema
+            );
+        }
+        for (i, accumulator) in self.energy.iter().enumerate() {
+            println!(
+                "Sensor {i}: energy={:.03} Wh",
+                accumulator.watt_hours()
+            );
+        }
+    }
+}